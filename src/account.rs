@@ -1,11 +1,11 @@
 use chrono::naive::NaiveDate as Date;
 use currency::Currency;
 
-use crate::util::{to_currency, to_f64};
+use crate::util::{symbol_for_currency_code, to_currency, to_f64};
 
 /// A simple unique ID for a specific account, simply used to identify which account we are looking
 /// at.
-#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, Hash)]
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, Hash, serde::Serialize, serde::Deserialize)]
 pub struct AccountID {
     pub id_val: u64,
 }
@@ -13,14 +13,20 @@ pub struct AccountID {
 /// A data structure representing the specified detals of an individual account. These details can
 /// be used to determine how transactions on specific accounts affect the account's value and how it
 /// changes over time.
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AccountSpec {
     /// The name of the account.
     pub name: String,
 
     /// The initial total account value.
+    #[serde(with = "crate::util::serde_currency")]
     pub initial_value: Currency,
 
+    /// The ISO 4217 currency code this account's value is denominated in, e.g. `"GBP"` or
+    /// `"USD"`. Transactions between accounts with different codes are converted through the
+    /// simulation's `ExchangeOracle`.
+    pub currency_code: String,
+
     // TODO: More flexible ways of expressing how account value changes from day to day.
     /// The effective interest rate per day on this account as a fraction of total account value.
     pub interest: f64,
@@ -35,19 +41,24 @@ pub struct AccountSpec {
 }
 
 impl AccountSpec {
+    /// The symbol conventionally used to format this account's currency, e.g. `"£"` for `"GBP"`.
+    fn symbol(&self) -> &'static str {
+        symbol_for_currency_code(&self.currency_code)
+    }
+
     /// Calculate the total value of this account after using it as a source for a transaction.
     pub fn source(&self, value: &Currency, out: &Currency) -> Currency {
-        value - to_currency(to_f64(out) * (1.0 + self.out_charge))
+        value - to_currency(to_f64(out) * (1.0 + self.out_charge), self.symbol())
     }
 
     /// Calculate the total value of this account after using it as a sink for a transaction.
     pub fn sink(&self, value: &Currency, in_: &Currency) -> Currency {
-        value + to_currency(to_f64(in_) * (1.0 - self.in_charge))
+        value + to_currency(to_f64(in_) * (1.0 - self.in_charge), self.symbol())
     }
 
     /// Calculate the total value of this account after a single day has passed.
     pub fn update(&self, value: &Currency) -> Currency {
-        to_currency(to_f64(value) * (1.0 + (self.interest / 365.0)))
+        to_currency(to_f64(value) * (1.0 + (self.interest / 365.0)), self.symbol())
     }
 }
 
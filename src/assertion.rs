@@ -0,0 +1,77 @@
+use chrono::naive::NaiveDate as Date;
+use currency::Currency;
+
+use crate::account::AccountID;
+use crate::util::to_f64;
+
+/// A claim that an account should hold roughly a given value on a given date, e.g. "Savings ≥
+/// £10,000 by 2025-01-01" expressed as an exact expected value plus a tolerance.
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct BalanceAssertion {
+    /// The account whose value is being asserted.
+    pub account: AccountID,
+
+    /// The date on which the assertion is checked.
+    #[serde(with = "crate::util::serde_date")]
+    pub date: Date,
+
+    /// The value the account is expected to hold on `date`.
+    #[serde(with = "crate::util::serde_currency")]
+    pub expected: Currency,
+
+    /// How far the actual value may differ from `expected`, in either direction, and still pass.
+    #[serde(with = "crate::util::serde_currency")]
+    pub tolerance: Currency,
+}
+
+impl BalanceAssertion {
+    /// Check `actual` against this assertion's `expected` value and `tolerance`, producing the
+    /// outcome to report.
+    pub fn evaluate(&self, actual: Currency) -> AssertionOutcome {
+        let passed = (to_f64(&actual) - to_f64(&self.expected)).abs() <= to_f64(&self.tolerance);
+        AssertionOutcome {
+            assertion: self.clone(),
+            actual,
+            passed,
+        }
+    }
+}
+
+/// The result of checking a `BalanceAssertion` against the simulated value of its account.
+#[derive(PartialEq, Clone, Debug)]
+pub struct AssertionOutcome {
+    /// The assertion this outcome was checked against.
+    pub assertion: BalanceAssertion,
+
+    /// The account's simulated value on the assertion's date.
+    pub actual: Currency,
+
+    /// Whether `actual` fell within `tolerance` of `expected`.
+    pub passed: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assertion() -> BalanceAssertion {
+        BalanceAssertion {
+            account: AccountID { id_val: 0 },
+            date: Date::from_ymd_opt(2023, 1, 1).unwrap(),
+            expected: Currency::from_str("£100.00").unwrap(),
+            tolerance: Currency::from_str("£1.00").unwrap(),
+        }
+    }
+
+    #[test]
+    fn evaluate_passes_within_tolerance() {
+        let outcome = assertion().evaluate(Currency::from_str("£100.50").unwrap());
+        assert!(outcome.passed);
+    }
+
+    #[test]
+    fn evaluate_fails_outside_tolerance() {
+        let outcome = assertion().evaluate(Currency::from_str("£105.00").unwrap());
+        assert!(!outcome.passed);
+    }
+}
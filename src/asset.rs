@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+
+use chrono::naive::NaiveDate as Date;
+use currency::Currency;
+
+use crate::util::{to_currency, to_f64};
+
+/// The specification of an account which holds a quantity of a commodity (shares, crypto,
+/// precious metals, ...) rather than a single cash balance. Priced via the simulation's
+/// `ExchangeOracle`, treating `commodity` as the `from` side of a conversion to `currency_code` —
+/// the same mechanism used to convert between ordinary currencies.
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AssetSpec {
+    /// The commodity held, e.g. `"AAPL"` or `"BTC"`.
+    pub commodity: String,
+
+    /// The ISO 4217 currency code this account's cost basis and gains are denominated in.
+    pub currency_code: String,
+
+    /// The quantity of `commodity` held at the start of the simulation.
+    pub quantity: f64,
+
+    /// The total amount paid for `quantity`, used to seed its opening lot.
+    #[serde(with = "crate::util::serde_currency")]
+    pub cost_basis: Currency,
+}
+
+/// A single purchase of some quantity of a commodity at a point in time, kept so later sales can
+/// be matched against it FIFO to compute a realized gain.
+#[derive(Clone, Debug)]
+pub struct AssetLot {
+    /// The quantity of the commodity still remaining from this purchase.
+    pub quantity: f64,
+
+    /// The price paid per unit, at the time of purchase.
+    pub unit_cost: Currency,
+
+    /// The date of purchase.
+    pub date: Date,
+}
+
+/// The simulated state of an asset account: its remaining lots, oldest first for FIFO matching,
+/// and the cumulative realized gain from all sales so far.
+#[derive(Clone, Debug)]
+pub struct AssetState {
+    pub lots: VecDeque<AssetLot>,
+    pub realized_gains: Currency,
+}
+
+impl AssetState {
+    /// Seed an asset account's state from its spec, treating the initial holding as a single
+    /// opening lot dated at the simulation's start date.
+    pub fn new(spec: &AssetSpec, start: Date, symbol: &str) -> AssetState {
+        let mut lots = VecDeque::new();
+        if spec.quantity > 0.0 {
+            let unit_cost = to_currency(to_f64(&spec.cost_basis) / spec.quantity, symbol);
+            lots.push_back(AssetLot {
+                quantity: spec.quantity,
+                unit_cost,
+                date: start,
+            });
+        }
+        AssetState {
+            lots,
+            realized_gains: to_currency(0.0, symbol),
+        }
+    }
+
+    /// The total quantity of the commodity currently held, across all remaining lots.
+    pub fn quantity(&self) -> f64 {
+        self.lots.iter().map(|lot| lot.quantity).sum()
+    }
+
+    /// The total amount paid for the commodity currently held, across all remaining lots.
+    pub fn cost_basis(&self, symbol: &str) -> Currency {
+        to_currency(
+            self.lots
+                .iter()
+                .map(|lot| lot.quantity * to_f64(&lot.unit_cost))
+                .sum(),
+            symbol,
+        )
+    }
+
+    /// Record a purchase of `quantity` at `unit_cost` on `date` as a new lot.
+    pub fn buy(&mut self, quantity: f64, unit_cost: Currency, date: Date) {
+        self.lots.push_back(AssetLot {
+            quantity,
+            unit_cost,
+            date,
+        });
+    }
+
+    /// Match a sale of `quantity` for `proceeds` against the oldest remaining lots (FIFO),
+    /// reducing or removing them as needed, and add the resulting gain (`proceeds` less the
+    /// matched cost basis) to `realized_gains`. Selling more than is held just sells whatever
+    /// remains.
+    pub fn sell(&mut self, quantity: f64, proceeds: Currency, symbol: &str) {
+        let mut remaining = quantity;
+        let mut cost_sold = 0.0;
+        while remaining > 0.0 {
+            let Some(lot) = self.lots.front_mut() else {
+                break;
+            };
+            let matched = remaining.min(lot.quantity);
+            cost_sold += matched * to_f64(&lot.unit_cost);
+            lot.quantity -= matched;
+            remaining -= matched;
+            if lot.quantity <= 0.0 {
+                self.lots.pop_front();
+            }
+        }
+
+        let gain = to_currency(to_f64(&proceeds) - cost_sold, symbol);
+        self.realized_gains = &self.realized_gains + gain;
+    }
+
+    /// The unrealized gain on the commodity still held: its value at `market_price` per unit, less
+    /// the cost basis of the lots making it up.
+    pub fn unrealized_gain(&self, market_price: f64, symbol: &str) -> Currency {
+        to_currency(
+            self.quantity() * market_price - to_f64(&self.cost_basis(symbol)),
+            symbol,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(quantity: f64, cost_basis: &str) -> AssetSpec {
+        AssetSpec {
+            commodity: "AAPL".to_string(),
+            currency_code: "GBP".to_string(),
+            quantity,
+            cost_basis: Currency::from_str(cost_basis).unwrap(),
+        }
+    }
+
+    #[test]
+    fn sell_against_a_fresh_account_realizes_a_gain_without_panicking() {
+        // Regression test: `AssetState::new` used to seed `realized_gains` with `Currency::new()`,
+        // whose symbol-less `None` panicked on the first `+` against a real-symbol delta.
+        let mut state = AssetState::new(&spec(0.0, "£0.00"), Date::from_ymd_opt(2023, 1, 1).unwrap(), "£");
+        state.buy(10.0, Currency::from_str("£1.00").unwrap(), Date::from_ymd_opt(2023, 1, 1).unwrap());
+
+        state.sell(10.0, Currency::from_str("£15.00").unwrap(), "£");
+
+        assert_eq!(state.realized_gains, Currency::from_str("£5.00").unwrap());
+    }
+
+    #[test]
+    fn sell_matches_lots_fifo_across_different_unit_costs() {
+        let mut state = AssetState::new(&spec(0.0, "£0.00"), Date::from_ymd_opt(2023, 1, 1).unwrap(), "£");
+        state.buy(5.0, Currency::from_str("£1.00").unwrap(), Date::from_ymd_opt(2023, 1, 1).unwrap());
+        state.buy(5.0, Currency::from_str("£2.00").unwrap(), Date::from_ymd_opt(2023, 2, 1).unwrap());
+
+        // Sells 5 units from the first lot (cost £5.00) and 2 from the second (cost £4.00), for
+        // £9.00 total cost against £20.00 proceeds.
+        state.sell(7.0, Currency::from_str("£20.00").unwrap(), "£");
+
+        assert_eq!(state.realized_gains, Currency::from_str("£11.00").unwrap());
+        assert_eq!(state.quantity(), 3.0);
+    }
+}
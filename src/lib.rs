@@ -0,0 +1,9 @@
+pub mod account;
+pub mod assertion;
+pub mod asset;
+pub mod oracle;
+pub mod persistence;
+pub mod simulation;
+pub mod tax;
+pub mod transaction;
+pub mod util;
@@ -1,12 +1,32 @@
+use std::path::Path;
+
 use rbudget::simulation::Simulation;
 
 fn main() {
     let mut sim = Simulation::default();
-    sim.load();
-    for (values, date) in sim.iter().take(5) {
-        println!("{}:", date);
-        for kv in values {
-            println!("Account {}, current value {}", kv.0.id_val, kv.1.to_string());
+
+    // Load a real budget file if one is present in the current working directory; otherwise fall back to the
+    // hardcoded demo data.
+    let budget_path = Path::new("budget.toml");
+    if budget_path.exists() {
+        sim.load_from_path(budget_path)
+            .expect("failed to load budget.toml");
+    } else {
+        sim.load();
+    }
+    for delta in sim.iter().take(5) {
+        println!("{}:", delta.date);
+        for (account, value) in delta.changes {
+            println!("Account {}, current value {}", account.id_val, value.to_string());
+        }
+        for asset in delta.asset_changes {
+            println!(
+                "Asset account {}, quantity {}, realized gains {}, unrealized gain {}",
+                asset.account.id_val,
+                asset.quantity,
+                asset.realized_gains.to_string(),
+                asset.unrealized_gain.to_string()
+            );
         }
     }
 }
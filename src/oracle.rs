@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::naive::NaiveDate as Date;
+
+/// A source of foreign exchange rates, used by the simulation to convert a transaction's value
+/// when its source and sink accounts are denominated in different currencies.
+pub trait ExchangeOracle {
+    /// The number of units of `to` one unit of `from` is worth on `date`.
+    fn rate(&self, from: &str, to: &str, date: Date) -> f64;
+}
+
+impl fmt::Debug for dyn ExchangeOracle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<dyn ExchangeOracle>")
+    }
+}
+
+/// An `ExchangeOracle` backed by a fixed table of rates, for currency pairs whose rate is assumed
+/// not to move over the course of a simulation.
+#[derive(Clone, Debug, Default)]
+pub struct StaticOracle {
+    rates: HashMap<(String, String), f64>,
+}
+
+impl StaticOracle {
+    /// Create an oracle with no configured rates. Unconfigured pairs convert at a rate of `1.0`.
+    pub fn new() -> StaticOracle {
+        StaticOracle::default()
+    }
+
+    /// Set the rate used to convert from `from` to `to`.
+    pub fn set_rate(&mut self, from: &str, to: &str, rate: f64) {
+        self.rates.insert((from.to_string(), to.to_string()), rate);
+    }
+}
+
+impl ExchangeOracle for StaticOracle {
+    fn rate(&self, from: &str, to: &str, _date: Date) -> f64 {
+        if from == to {
+            return 1.0;
+        }
+        self.rates
+            .get(&(from.to_string(), to.to_string()))
+            .copied()
+            .unwrap_or(1.0)
+    }
+}
+
+/// An `ExchangeOracle` backed by a series of dated quotes per currency pair, mirroring a
+/// commodities/market-data price oracle. Rates between two quoted dates are linearly
+/// interpolated; dates before the first quote or after the last quote use the nearest quote's
+/// rate.
+#[derive(Clone, Debug, Default)]
+pub struct HistoricalOracle {
+    /// Quotes for each currency pair, kept sorted by date.
+    quotes: HashMap<(String, String), Vec<(Date, f64)>>,
+}
+
+impl HistoricalOracle {
+    /// Create an oracle with no configured quotes. Unconfigured pairs convert at a rate of `1.0`.
+    pub fn new() -> HistoricalOracle {
+        HistoricalOracle::default()
+    }
+
+    /// Add a dated quote for converting from `from` to `to`.
+    pub fn add_quote(&mut self, from: &str, to: &str, date: Date, rate: f64) {
+        let series = self
+            .quotes
+            .entry((from.to_string(), to.to_string()))
+            .or_default();
+        series.push((date, rate));
+        series.sort_by_key(|(d, _)| *d);
+    }
+}
+
+impl ExchangeOracle for HistoricalOracle {
+    fn rate(&self, from: &str, to: &str, date: Date) -> f64 {
+        if from == to {
+            return 1.0;
+        }
+
+        let Some(series) = self.quotes.get(&(from.to_string(), to.to_string())) else {
+            return 1.0;
+        };
+        if series.is_empty() {
+            return 1.0;
+        }
+
+        // Before the first quote or after the last quote, clamp to the nearest one.
+        if date <= series[0].0 {
+            return series[0].1;
+        }
+        if date >= series[series.len() - 1].0 {
+            return series[series.len() - 1].1;
+        }
+
+        // Otherwise linearly interpolate between the two quotes either side of `date`.
+        let next_idx = series.partition_point(|(d, _)| *d <= date);
+        let (before_date, before_rate) = series[next_idx - 1];
+        let (after_date, after_rate) = series[next_idx];
+
+        let span = (after_date - before_date).num_days() as f64;
+        let offset = (date - before_date).num_days() as f64;
+        before_rate + (after_rate - before_rate) * (offset / span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn static_oracle_defaults_unconfigured_pairs_to_one() {
+        let oracle = StaticOracle::new();
+        assert_eq!(oracle.rate("USD", "GBP", Date::from_ymd_opt(2023, 1, 1).unwrap()), 1.0);
+    }
+
+    #[test]
+    fn static_oracle_returns_the_configured_rate() {
+        let mut oracle = StaticOracle::new();
+        oracle.set_rate("USD", "GBP", 0.8);
+        assert_eq!(oracle.rate("USD", "GBP", Date::from_ymd_opt(2023, 1, 1).unwrap()), 0.8);
+    }
+
+    #[test]
+    fn historical_oracle_interpolates_linearly_between_quotes() {
+        let mut oracle = HistoricalOracle::new();
+        oracle.add_quote("USD", "GBP", Date::from_ymd_opt(2023, 1, 1).unwrap(), 0.8);
+        oracle.add_quote("USD", "GBP", Date::from_ymd_opt(2023, 1, 11).unwrap(), 1.0);
+
+        let rate = oracle.rate("USD", "GBP", Date::from_ymd_opt(2023, 1, 6).unwrap());
+        assert!((rate - 0.9).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn historical_oracle_clamps_to_the_nearest_quote_outside_its_range() {
+        let mut oracle = HistoricalOracle::new();
+        oracle.add_quote("USD", "GBP", Date::from_ymd_opt(2023, 1, 1).unwrap(), 0.8);
+        oracle.add_quote("USD", "GBP", Date::from_ymd_opt(2023, 1, 11).unwrap(), 1.0);
+
+        assert_eq!(oracle.rate("USD", "GBP", Date::from_ymd_opt(2022, 12, 1).unwrap()), 0.8);
+        assert_eq!(oracle.rate("USD", "GBP", Date::from_ymd_opt(2023, 2, 1).unwrap()), 1.0);
+    }
+}
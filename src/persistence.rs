@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use chrono::naive::NaiveDate as Date;
+
+use crate::{
+    account::{AccountID, AccountSpec},
+    assertion::BalanceAssertion,
+    asset::AssetSpec,
+    simulation::Simulation,
+    tax::TaxRule,
+    transaction::Transaction,
+};
+
+/// A single entry in a persisted budget file, pairing an `AccountID` with the `AccountSpec` it
+/// identifies. Accounts are stored as a list rather than a map because TOML (and JSON, when keys
+/// aren't strings) can't represent `AccountID` directly as a table key.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AccountEntry {
+    pub id: AccountID,
+    pub spec: AccountSpec,
+}
+
+/// As `AccountEntry`, but for commodity-holding accounts.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AssetEntry {
+    pub id: AccountID,
+    pub spec: AssetSpec,
+}
+
+/// The on-disk representation of a `Simulation`: every account, every asset account, every
+/// transaction, tax rule and balance assertion, and the start date the simulation runs from.
+///
+/// The `oracle` field of `Simulation` has no equivalent here: it's a trait object, and there's no
+/// established way in this codebase to serialize one of those back into a concrete oracle type.
+/// Callers that need exchange rates persisted have to set `Simulation::oracle` in code after
+/// loading a `BudgetFile`.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct BudgetFile {
+    pub accounts: Vec<AccountEntry>,
+    #[serde(default)]
+    pub assets: Vec<AssetEntry>,
+    pub transactions: Vec<Transaction>,
+    #[serde(default)]
+    pub tax_rules: Vec<TaxRule>,
+    #[serde(default)]
+    pub assertions: Vec<BalanceAssertion>,
+    #[serde(with = "crate::util::serde_date")]
+    pub start: Date,
+}
+
+/// An error encountered while loading or saving a `Simulation` to disk.
+#[derive(Debug)]
+pub enum PersistenceError {
+    /// The budget file could not be read or written.
+    Io(std::io::Error),
+    /// The budget file's contents could not be deserialized.
+    Deserialize(toml::de::Error),
+    /// The simulation could not be serialized into a budget file.
+    Serialize(toml::ser::Error),
+    /// A transaction in the budget file referenced an account ID that isn't in either `accounts`
+    /// or `assets`.
+    InvalidAccountID { id: AccountID },
+}
+
+impl From<std::io::Error> for PersistenceError {
+    fn from(err: std::io::Error) -> Self {
+        PersistenceError::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for PersistenceError {
+    fn from(err: toml::de::Error) -> Self {
+        PersistenceError::Deserialize(err)
+    }
+}
+
+impl From<toml::ser::Error> for PersistenceError {
+    fn from(err: toml::ser::Error) -> Self {
+        PersistenceError::Serialize(err)
+    }
+}
+
+impl Simulation {
+    /// Load a simulation's accounts, asset accounts, transactions, tax rules, assertions and
+    /// start date from a TOML budget file at `path`, replacing whatever this `Simulation`
+    /// currently holds.
+    pub fn load_from_path(&mut self, path: &Path) -> Result<(), PersistenceError> {
+        let contents = fs::read_to_string(path)?;
+        let file: BudgetFile = toml::from_str(&contents)?;
+
+        let accounts: HashMap<AccountID, AccountSpec> = file
+            .accounts
+            .into_iter()
+            .map(|entry| (entry.id, entry.spec))
+            .collect();
+        let assets: HashMap<AccountID, AssetSpec> = file
+            .assets
+            .into_iter()
+            .map(|entry| (entry.id, entry.spec))
+            .collect();
+
+        // Unlike `Transaction::single`/`repeating`/`repeating_until`, a deserialized `Transaction`
+        // never had its account IDs checked against an actual `Simulation` — validate them here,
+        // rather than letting a typo'd or stale ID panic deep in `SimulationIterator::next`.
+        for t in &file.transactions {
+            if !accounts.contains_key(&t.source) && !assets.contains_key(&t.source) {
+                return Err(PersistenceError::InvalidAccountID { id: t.source });
+            }
+            if !accounts.contains_key(&t.sink) && !assets.contains_key(&t.sink) {
+                return Err(PersistenceError::InvalidAccountID { id: t.sink });
+            }
+        }
+
+        self.accounts = accounts;
+        self.assets = assets;
+        self.transactions = file.transactions;
+        self.tax_rules = file.tax_rules;
+        self.assertions = file.assertions;
+        self.start = file.start;
+
+        Ok(())
+    }
+
+    /// Save this simulation's accounts, asset accounts, transactions, tax rules, assertions and
+    /// start date to a TOML budget file at `path`, so a user can later edit it by hand and re-run
+    /// the simulation.
+    pub fn save_to_path(&self, path: &Path) -> Result<(), PersistenceError> {
+        let file = BudgetFile {
+            accounts: self
+                .accounts
+                .iter()
+                .map(|(id, spec)| AccountEntry {
+                    id: *id,
+                    spec: spec.clone(),
+                })
+                .collect(),
+            assets: self
+                .assets
+                .iter()
+                .map(|(id, spec)| AssetEntry {
+                    id: *id,
+                    spec: spec.clone(),
+                })
+                .collect(),
+            transactions: self.transactions.clone(),
+            tax_rules: self.tax_rules.clone(),
+            assertions: self.assertions.clone(),
+            start: self.start,
+        };
+
+        let contents = toml::to_string_pretty(&file)?;
+        fs::write(path, contents)?;
+
+        Ok(())
+    }
+}
@@ -1,12 +1,17 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use chrono::{Days, NaiveDate};
 use currency::Currency;
 
 use crate::{
     account::{AccountID, AccountSpec},
+    assertion::{AssertionOutcome, BalanceAssertion},
+    asset::{AssetSpec, AssetState},
+    oracle::ExchangeOracle,
+    tax::{payment_date, tax_year, TaxPaymentDay, TaxRule},
     transaction::Transaction,
-    util::DateInterval,
+    util::{symbol_for_currency_code, to_currency, to_f64, DateInterval},
 };
 
 // TODO: Can we do something equivalent to a 'mutable singleton' in Rust?
@@ -17,22 +22,40 @@ pub struct Simulation {
     /// All accounts which may be used as a sink or source for transactions.
     pub accounts: HashMap<AccountID, AccountSpec>,
 
+    /// All commodity-holding accounts which may be used as a sink or source for transactions.
+    /// Transactions involving one of these accounts are treated as a buy or sell of the
+    /// commodity rather than a cash transfer.
+    pub assets: HashMap<AccountID, AssetSpec>,
+
     /// All specified transactions which may take place.
     pub transactions: Vec<Transaction>,
 
     /// The start date for the simulation.
     pub start: NaiveDate,
+
+    /// The source of exchange rates used to convert a transaction's value when its source and
+    /// sink accounts are denominated in different currencies. Unconfigured pairs convert at a
+    /// rate of `1.0`.
+    pub oracle: Option<Rc<dyn ExchangeOracle>>,
+
+    /// Rules which accrue a tax liability as transactions credit their `applies_to` account, and
+    /// settle that liability on their configured `payment_day`.
+    pub tax_rules: Vec<TaxRule>,
+
+    /// Claims about account values on specific dates, checked as the simulation reaches them.
+    pub assertions: Vec<BalanceAssertion>,
 }
 
 impl Simulation {
-    /// Load values for accounts and transactions to be used when running the simulation.
+    /// Load a fixed set of demo accounts and transactions, for callers with no budget file of
+    /// their own to load via `load_from_path`.
     pub fn load(&mut self) {
-        // TODO: Read accounts and transactions from a file. For now just specify some defaults.
         self.accounts.insert(
             AccountID { id_val: 0 },
             AccountSpec {
                 name: "Bank".to_string(),
                 initial_value: Currency::from_str("£1000.00").unwrap(),
+                currency_code: "GBP".to_string(),
                 interest: 0.0,
                 out_charge: 0.0,
                 in_charge: 0.0,
@@ -43,6 +66,7 @@ impl Simulation {
             AccountSpec {
                 name: "Savings".to_string(),
                 initial_value: Currency::from_str("£500.00").unwrap(),
+                currency_code: "GBP".to_string(),
                 interest: 0.03,
                 out_charge: 0.0,
                 in_charge: 0.0,
@@ -53,6 +77,7 @@ impl Simulation {
             AccountSpec {
                 name: "Employer".to_string(),
                 initial_value: Currency::from_str("£0.00").unwrap(),
+                currency_code: "GBP".to_string(),
                 interest: 0.0,
                 out_charge: -1.0,
                 in_charge: 0.0,
@@ -63,6 +88,7 @@ impl Simulation {
             AccountSpec {
                 name: "Costs".to_string(),
                 initial_value: Currency::from_str("£0.00").unwrap(),
+                currency_code: "GBP".to_string(),
                 interest: 0.0,
                 out_charge: 0.0,
                 in_charge: 1.0,
@@ -92,82 +118,404 @@ impl Simulation {
         self.start = NaiveDate::from_ymd_opt(2023, 02, 23).unwrap();
     }
 
-    pub fn iter(self) -> SimulationIterator {
-        // TODO: Make `sim` a reference so we don't have to do so much cloning.
-        let clone = self.clone();
+    /// Iterate the simulation day by day, yielding only the accounts whose value changes each
+    /// day. Borrows `self` rather than cloning it, since a multi-year simulation otherwise has no
+    /// need to keep its own copy of every account and transaction alive.
+    pub fn iter(&self) -> SimulationIterator<'_> {
         SimulationIterator {
-            sim: clone,
+            sim: self,
             values: self
                 .accounts
                 .iter()
                 .map(|kv| (kv.0.clone(), kv.1.initial_value.clone()))
                 .collect(),
+            asset_state: self
+                .assets
+                .iter()
+                .map(|(id, spec)| {
+                    let symbol = symbol_for_currency_code(&spec.currency_code);
+                    (*id, AssetState::new(spec, self.start, symbol))
+                })
+                .collect(),
             date: self.start,
+            accrued_tax: HashMap::new(),
+            assertion_outcomes: Vec::new(),
         }
     }
+
+    /// Run the simulation up to the latest date any `BalanceAssertion` is checked on, and return
+    /// the outcome of every assertion reached on a cash account. Returns an empty vec if there are
+    /// no assertions to check.
+    pub fn check_assertions(&self) -> Vec<AssertionOutcome> {
+        let Some(latest) = self.assertions.iter().map(|a| a.date).max() else {
+            return Vec::new();
+        };
+
+        let mut it = self.iter();
+        while it.date <= latest {
+            it.next();
+        }
+        it.assertion_outcomes
+    }
+}
+
+/// A single day's worth of account value changes, yielded by `SimulationIterator`. Only accounts
+/// whose value changed by more than `CHANGE_THRESHOLD` are listed; everything else is unchanged
+/// from the last delta (or the relevant account's `initial_value`, if this is the first delta
+/// that mentions it). Callers wanting a full snapshot can fold `changes` over a running base
+/// state, or call `SimulationIterator::values_snapshot` directly.
+#[derive(Clone, Debug)]
+pub struct DayDelta {
+    /// The date these changes apply to.
+    pub date: NaiveDate,
+
+    /// The accounts whose value changed today, and their new value.
+    pub changes: Vec<(AccountID, Currency)>,
+
+    /// The asset accounts whose held quantity or realized gains changed today. A change in
+    /// unrealized gain alone, from a move in market price, does not put an account here.
+    pub asset_changes: Vec<AssetDelta>,
+}
+
+/// A single asset account's state for a `DayDelta`, reported whenever its held quantity or
+/// realized gains changed today. Days on which only the market price moves produce no
+/// `AssetDelta`, so `unrealized_gain` is current only as of the last reported day.
+#[derive(Clone, Debug)]
+pub struct AssetDelta {
+    /// The asset account this delta describes.
+    pub account: AccountID,
+
+    /// The quantity of the commodity held after today's transactions.
+    pub quantity: f64,
+
+    /// The cumulative realized gain from all sales out of this account so far.
+    pub realized_gains: Currency,
+
+    /// `quantity * market_price(date) - remaining_cost_basis`, i.e. the gain that would be
+    /// realized if the remaining holding were sold today.
+    pub unrealized_gain: Currency,
 }
 
+/// The minimum absolute change in an account's value, in the account's major currency unit,
+/// needed for that change to be worth reporting in a `DayDelta`. Interest accrues on every
+/// account every day, so without a threshold almost every account would show up in almost every
+/// delta, even when the change rounds away to nothing meaningful.
+const CHANGE_THRESHOLD: f64 = 0.005;
+
 /// An iterator type which provides values of accounts over a forward progression of time.
 #[derive(Clone, Debug)]
-pub struct SimulationIterator {
-    /// Data relating to the original state of the simulation.
-    pub sim: Simulation,
+pub struct SimulationIterator<'a> {
+    /// The simulation this iterator is running, borrowed for the lifetime of the iteration.
+    pub sim: &'a Simulation,
 
     /// The current values of all the accounts on this iteration of the simulation.
     pub values: HashMap<AccountID, Currency>,
 
+    /// The current lots and cumulative realized gains of all the asset accounts on this
+    /// iteration of the simulation.
+    pub asset_state: HashMap<AccountID, AssetState>,
+
     /// The current date on this iteration of the simulation.
     pub date: NaiveDate,
+
+    /// Tax liability accrued but not yet paid, keyed by the index of the `TaxRule` in
+    /// `sim.tax_rules` that accrued it and the tax year it was accrued in.
+    pub accrued_tax: HashMap<(usize, i32), Currency>,
+
+    /// The outcome of every `BalanceAssertion` whose date has been reached so far. Assertions on
+    /// an asset account or an unknown account ID produce no outcome.
+    pub assertion_outcomes: Vec<AssertionOutcome>,
 }
 
-impl IntoIterator for Simulation {
-    type Item = (HashMap<AccountID, Currency>, NaiveDate);
-    type IntoIter = SimulationIterator;
+impl<'a> SimulationIterator<'a> {
+    /// The full current value of every account, for when a `DayDelta`'s changes alone aren't
+    /// enough. Unlike the deltas yielded by `next`, this clones the whole account map.
+    pub fn values_snapshot(&self) -> HashMap<AccountID, Currency> {
+        self.values.clone()
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        // TODO: Make `sim` a reference so we don't have to do so much cloning.
-        let accounts = self.accounts.clone();
-        let date = self.start;
-        SimulationIterator {
-            sim: self,
-            values: accounts
-                .into_iter()
-                .map(|kv| (kv.0, kv.1.initial_value))
-                .collect(),
-            date,
+    /// Accrued-but-unpaid tax liability, summed per account across all tax years and rules that
+    /// settle from that account. Lets callers see upcoming tax bills before they fall due.
+    pub fn accrued_tax_by_account(&self) -> HashMap<AccountID, Currency> {
+        let mut by_account: HashMap<AccountID, Currency> = HashMap::new();
+        for (&(rule_idx, _year), liability) in self.accrued_tax.iter() {
+            let rule = &self.sim.tax_rules[rule_idx];
+            let payer = rule.payer();
+            let currency_code = self
+                .sim
+                .accounts
+                .get(&payer)
+                .map(|spec| spec.currency_code.as_str())
+                .or_else(|| self.sim.assets.get(&payer).map(|spec| spec.currency_code.as_str()))
+                .unwrap_or_default();
+            let entry = by_account
+                .entry(payer)
+                .or_insert_with(|| to_currency(0.0, symbol_for_currency_code(currency_code)));
+            *entry = &*entry + liability.clone();
+        }
+        by_account
+    }
+
+    /// Settle any tax liability accrued under `TaxPaymentDay::OnClose` rules immediately, e.g.
+    /// when a simulation is being wound up rather than iterated day by day. Liabilities tied to a
+    /// fixed `payment_day` are left untouched, since they settle automatically as the simulation
+    /// reaches their due date.
+    pub fn settle_on_close(&mut self) {
+        let due: Vec<(usize, i32)> = self
+            .accrued_tax
+            .keys()
+            .copied()
+            .filter(|(rule_idx, _year)| {
+                matches!(
+                    self.sim.tax_rules[*rule_idx].payment_day,
+                    TaxPaymentDay::OnClose
+                )
+            })
+            .collect();
+
+        for key in due {
+            self.pay_liability(key);
+        }
+    }
+
+    /// Debit the liability stored under `key` from its rule's payer account and remove it from
+    /// the accrued map.
+    fn pay_liability(&mut self, key: (usize, i32)) {
+        let Some(liability) = self.accrued_tax.remove(&key) else {
+            return;
+        };
+        let payer = self.sim.tax_rules[key.0].payer();
+        if let Some(payer_val) = self.values.get_mut(&payer) {
+            *payer_val = &*payer_val - liability;
         }
     }
+
+    /// The current market price of one unit of `commodity`, denominated in `currency_code`, via
+    /// the simulation's exchange oracle (treating the commodity as the `from` side of a
+    /// conversion). Unconfigured commodities price at `1.0`, matching the oracle's fallback for
+    /// unconfigured currency pairs.
+    fn commodity_price(&self, commodity: &str, currency_code: &str) -> f64 {
+        self.sim
+            .oracle
+            .as_ref()
+            .map_or(1.0, |oracle| oracle.rate(commodity, currency_code, self.date))
+    }
 }
 
-impl Iterator for SimulationIterator {
-    type Item = (HashMap<AccountID, Currency>, NaiveDate);
+impl<'a> Iterator for SimulationIterator<'a> {
+    type Item = DayDelta;
 
     fn next(&mut self) -> Option<Self::Item> {
+        // Remember each account's value from just before it's mutated today, so we can tell how
+        // much it changed once we're done. Captured lazily, account by account, right where each
+        // mutation happens below, rather than cloning the whole map up front regardless of how
+        // many accounts actually change today.
+        let mut day_start: HashMap<AccountID, Currency> = HashMap::new();
+        let mut day_start_assets: HashMap<AccountID, (f64, Currency)> = HashMap::new();
+
         // Iterate through the relevant transactions, IE those which occur on the current date.
         for t in self.sim.transactions.iter().filter(|t| t.occurs(self.date)) {
             // TODO: Error handling if account ID doesn't exist.
 
+            // A transaction touching an asset account is a buy or a sell of its commodity,
+            // rather than a cash transfer, so it's handled entirely separately below.
+            let is_source_asset = self.sim.assets.contains_key(&t.source);
+            let is_sink_asset = self.sim.assets.contains_key(&t.sink);
+            if is_source_asset || is_sink_asset {
+                if is_sink_asset && !is_source_asset {
+                    // Buying into an asset account: debit the cash source as usual, then record
+                    // a new lot sized by how much commodity that cash bought at today's price.
+                    let source_spec = self.sim.accounts.get(&t.source).unwrap();
+                    let source_val = self.values.get_mut(&t.source).unwrap();
+                    day_start.entry(t.source).or_insert_with(|| source_val.clone());
+                    *source_val = source_spec.source(source_val, &t.value);
+
+                    let asset_spec = self.sim.assets.get(&t.sink).unwrap();
+                    let symbol = symbol_for_currency_code(&asset_spec.currency_code);
+                    let cost = if source_spec.currency_code == asset_spec.currency_code {
+                        t.value.clone()
+                    } else {
+                        let rate = self.sim.oracle.as_ref().map_or(1.0, |oracle| {
+                            oracle.rate(&source_spec.currency_code, &asset_spec.currency_code, self.date)
+                        });
+                        to_currency(to_f64(&t.value) * rate, symbol)
+                    };
+                    let price = self.commodity_price(&asset_spec.commodity, &asset_spec.currency_code);
+                    let quantity = to_f64(&cost) / price;
+                    let state = self.asset_state.get_mut(&t.sink).unwrap();
+                    day_start_assets
+                        .entry(t.sink)
+                        .or_insert_with(|| (state.quantity(), state.realized_gains.clone()));
+                    state.buy(quantity, to_currency(price, symbol), self.date);
+                } else if is_source_asset && !is_sink_asset {
+                    // Selling out of an asset account: match the sale against its lots FIFO to
+                    // realize a gain, then credit the cash sink with the proceeds as usual.
+                    let asset_spec = self.sim.assets.get(&t.source).unwrap();
+                    let symbol = symbol_for_currency_code(&asset_spec.currency_code);
+                    let price = self.commodity_price(&asset_spec.commodity, &asset_spec.currency_code);
+                    let quantity = to_f64(&t.value) / price;
+                    let state = self.asset_state.get_mut(&t.source).unwrap();
+                    day_start_assets
+                        .entry(t.source)
+                        .or_insert_with(|| (state.quantity(), state.realized_gains.clone()));
+                    state.sell(quantity, t.value.clone(), symbol);
+
+                    let sink_spec = self.sim.accounts.get(&t.sink).unwrap();
+                    let sink_value = if sink_spec.currency_code == asset_spec.currency_code {
+                        t.value.clone()
+                    } else {
+                        let rate = self.sim.oracle.as_ref().map_or(1.0, |oracle| {
+                            oracle.rate(&asset_spec.currency_code, &sink_spec.currency_code, self.date)
+                        });
+                        to_currency(to_f64(&t.value) * rate, symbol_for_currency_code(&sink_spec.currency_code))
+                    };
+                    let sink_val = self.values.get_mut(&t.sink).unwrap();
+                    day_start.entry(t.sink).or_insert_with(|| sink_val.clone());
+                    *sink_val = sink_spec.sink(sink_val, &sink_value);
+
+                    // The proceeds credit the cash sink, so accrue tax on them as for any credit.
+                    let sink_symbol = symbol_for_currency_code(&sink_spec.currency_code);
+                    for (idx, rule) in self.sim.tax_rules.iter().enumerate() {
+                        if rule.applies_to != t.sink {
+                            continue;
+                        }
+                        let liability = to_currency(to_f64(&sink_value) * rule.rate, sink_symbol);
+                        let entry = self
+                            .accrued_tax
+                            .entry((idx, tax_year(self.date)))
+                            .or_insert_with(|| to_currency(0.0, sink_symbol));
+                        *entry = &*entry + liability;
+                    }
+                }
+                // A transaction between two asset accounts isn't a supported shape; it's simply
+                // skipped, the same as the occurrence not happening at all.
+                continue;
+            }
+
             // Update source and sink account values according to their specification on how to
             // handle money being transferred out and in respectively.
             let source_spec = self.sim.accounts.get(&t.source).unwrap();
             let source_val = self.values.get_mut(&t.source).unwrap();
+            day_start.entry(t.source).or_insert_with(|| source_val.clone());
             *source_val = source_spec.source(source_val, &t.value);
 
+            // If the sink account is denominated in a different currency to the source account,
+            // convert the transferred value through the simulation's exchange oracle before
+            // crediting it, at the rate in effect on the current date.
             let sink_spec = self.sim.accounts.get(&t.sink).unwrap();
+            let sink_value = if sink_spec.currency_code == source_spec.currency_code {
+                t.value.clone()
+            } else {
+                let rate = self.sim.oracle.as_ref().map_or(1.0, |oracle| {
+                    oracle.rate(&source_spec.currency_code, &sink_spec.currency_code, self.date)
+                });
+                to_currency(to_f64(&t.value) * rate, symbol_for_currency_code(&sink_spec.currency_code))
+            };
             let sink_val = self.values.get_mut(&t.sink).unwrap();
-            *sink_val = sink_spec.sink(sink_val, &t.value);
+            day_start.entry(t.sink).or_insert_with(|| sink_val.clone());
+            *sink_val = sink_spec.sink(sink_val, &sink_value);
+
+            // Accrue tax liability on any rule that taxes the sink account, keyed by the tax
+            // year the credit falls in so it can be settled on the correct payment date.
+            for (idx, rule) in self.sim.tax_rules.iter().enumerate() {
+                if rule.applies_to != t.sink {
+                    continue;
+                }
+                let liability = to_currency(
+                    to_f64(&sink_value) * rule.rate,
+                    symbol_for_currency_code(&sink_spec.currency_code),
+                );
+                let entry = self
+                    .accrued_tax
+                    .entry((idx, tax_year(self.date)))
+                    .or_insert_with(|| to_currency(0.0, symbol_for_currency_code(&sink_spec.currency_code)));
+                *entry = &*entry + liability;
+            }
+        }
+
+        // Settle any tax liability whose payment date has arrived.
+        let due: Vec<(usize, i32)> = self
+            .accrued_tax
+            .keys()
+            .copied()
+            .filter(|&(rule_idx, year)| {
+                payment_date(self.sim.tax_rules[rule_idx].payment_day, year) == Some(self.date)
+            })
+            .collect();
+        for key in due {
+            self.pay_liability(key);
         }
 
         // Iterate through all accounts and allow them to apply whatever update is necessary to
-        // their values over the course of a single day.
+        // their values over the course of a single day. Accounts with no interest have nothing to
+        // update, so they're skipped entirely rather than cloned into `day_start` for nothing.
         for (a, val) in self.values.iter_mut() {
             let spec = self.sim.accounts.get(&a).unwrap();
+            if spec.interest == 0.0 {
+                continue;
+            }
+            day_start.entry(*a).or_insert_with(|| val.clone());
             *val = spec.update(val);
         }
 
+        // Check any balance assertions due on today's date against today's simulated values.
+        for assertion in self.sim.assertions.iter().filter(|a| a.date == self.date) {
+            if let Some(actual) = self.values.get(&assertion.account) {
+                self.assertion_outcomes.push(assertion.evaluate(actual.clone()));
+            }
+        }
+
         // Advance the date by one day.
         self.date = self.date + Days::new(1);
 
-        Some((self.values.clone(), self.date))
+        // Only report accounts whose value moved by more than the reporting threshold today.
+        let changes = self
+            .values
+            .iter()
+            .filter(|(account, new_val)| {
+                // An account absent from `day_start` was never touched today, so it's unchanged —
+                // not a change worth reporting.
+                day_start
+                    .get(account)
+                    .map_or(false, |old_val| (to_f64(new_val) - to_f64(old_val)).abs() > CHANGE_THRESHOLD)
+            })
+            .map(|(account, new_val)| (*account, new_val.clone()))
+            .collect();
+
+        // Likewise, only report asset accounts whose held quantity or realized gains moved today.
+        let asset_changes = self
+            .asset_state
+            .iter()
+            .filter_map(|(account, state)| {
+                let asset_spec = self.sim.assets.get(account).unwrap();
+                let symbol = symbol_for_currency_code(&asset_spec.currency_code);
+                let price = self.commodity_price(&asset_spec.commodity, &asset_spec.currency_code);
+                let quantity = state.quantity();
+                let unrealized_gain = state.unrealized_gain(price, symbol);
+
+                let (old_quantity, old_realized) = day_start_assets
+                    .get(account)
+                    .cloned()
+                    .unwrap_or_else(|| (quantity, state.realized_gains.clone()));
+                let changed = (quantity - old_quantity).abs() > f64::EPSILON
+                    || (to_f64(&state.realized_gains) - to_f64(&old_realized)).abs() > CHANGE_THRESHOLD;
+                if !changed {
+                    return None;
+                }
+
+                Some(AssetDelta {
+                    account: *account,
+                    quantity,
+                    realized_gains: state.realized_gains.clone(),
+                    unrealized_gain,
+                })
+            })
+            .collect();
+
+        Some(DayDelta {
+            date: self.date,
+            changes,
+            asset_changes,
+        })
     }
 }
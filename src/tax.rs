@@ -0,0 +1,118 @@
+use chrono::{Datelike, NaiveDate as Date};
+
+use crate::account::AccountID;
+
+/// The date on which a tax year's accrued liability falls due for payment.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash, serde::Serialize, serde::Deserialize)]
+pub enum TaxPaymentDay {
+    /// Payment falls due on a fixed day and month of the year following the tax year the
+    /// liability was accrued in, e.g. `Day { month: 4, day: 6 }` for the UK's 6 April deadline.
+    Day { month: u32, day: u32 },
+
+    /// Payment falls due only once the simulation is closed out, rather than on a recurring date.
+    OnClose,
+}
+
+impl Default for TaxPaymentDay {
+    /// Defaults to 6 April, the start of the UK tax year.
+    fn default() -> Self {
+        TaxPaymentDay::Day { month: 4, day: 6 }
+    }
+}
+
+/// A rule which accrues a tax liability whenever a transaction credits `applies_to`, and settles
+/// that liability on `payment_day`.
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct TaxRule {
+    /// The rate at which incoming value to `applies_to` is taxed, as a fraction of that value.
+    pub rate: f64,
+
+    /// The account whose incoming transactions accrue this tax liability.
+    pub applies_to: AccountID,
+
+    /// The date on which the liability accrued for a given tax year falls due.
+    pub payment_day: TaxPaymentDay,
+
+    /// The account the liability is paid from when it falls due. Defaults to `applies_to` itself
+    /// when not set, e.g. for an income tax paid directly out of the account that earned it.
+    pub pays_from: Option<AccountID>,
+}
+
+impl TaxRule {
+    /// The account this rule's liability is paid from.
+    pub fn payer(&self) -> AccountID {
+        self.pays_from.unwrap_or(self.applies_to)
+    }
+}
+
+/// The UK tax year a date falls in, identified by the calendar year it starts in. The tax year
+/// starting 6 April 2023 runs to 5 April 2024 and is identified as `2023`.
+pub fn tax_year(date: Date) -> i32 {
+    let year_start = Date::from_ymd_opt(date.year(), 4, 6).unwrap();
+    if date >= year_start {
+        date.year()
+    } else {
+        date.year() - 1
+    }
+}
+
+/// The date on which liability accrued during `accrual_year` falls due under `payment_day`, if
+/// it falls due on a fixed date at all. Income accrued in tax year `N` is paid on `payment_day`
+/// of year `N + 1`.
+pub fn payment_date(payment_day: TaxPaymentDay, accrual_year: i32) -> Option<Date> {
+    match payment_day {
+        TaxPaymentDay::Day { month, day } => Date::from_ymd_opt(accrual_year + 1, month, day),
+        TaxPaymentDay::OnClose => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tax_year_before_april_sixth_belongs_to_the_previous_calendar_year() {
+        assert_eq!(tax_year(Date::from_ymd_opt(2023, 4, 5).unwrap()), 2022);
+        assert_eq!(tax_year(Date::from_ymd_opt(2023, 1, 1).unwrap()), 2022);
+    }
+
+    #[test]
+    fn tax_year_on_or_after_april_sixth_belongs_to_that_calendar_year() {
+        assert_eq!(tax_year(Date::from_ymd_opt(2023, 4, 6).unwrap()), 2023);
+        assert_eq!(tax_year(Date::from_ymd_opt(2023, 12, 31).unwrap()), 2023);
+        assert_eq!(tax_year(Date::from_ymd_opt(2022, 12, 31).unwrap()), 2022);
+    }
+
+    #[test]
+    fn payment_date_for_a_fixed_day_falls_in_the_year_after_accrual() {
+        let day = TaxPaymentDay::Day { month: 4, day: 6 };
+        assert_eq!(payment_date(day, 2023), Date::from_ymd_opt(2024, 4, 6));
+    }
+
+    #[test]
+    fn payment_date_for_on_close_never_falls_due_on_a_fixed_date() {
+        assert_eq!(payment_date(TaxPaymentDay::OnClose, 2023), None);
+    }
+
+    #[test]
+    fn payer_defaults_to_applies_to_when_pays_from_is_unset() {
+        let rule = TaxRule {
+            rate: 0.2,
+            applies_to: AccountID { id_val: 0 },
+            payment_day: TaxPaymentDay::default(),
+            pays_from: None,
+        };
+        assert_eq!(rule.payer(), AccountID { id_val: 0 });
+    }
+
+    #[test]
+    fn payer_uses_pays_from_when_set() {
+        let rule = TaxRule {
+            rate: 0.2,
+            applies_to: AccountID { id_val: 0 },
+            payment_day: TaxPaymentDay::default(),
+            pays_from: Some(AccountID { id_val: 1 }),
+        };
+        assert_eq!(rule.payer(), AccountID { id_val: 1 });
+    }
+}
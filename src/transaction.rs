@@ -1,15 +1,16 @@
 use crate::account::AccountID;
 use crate::simulation::Simulation;
-use crate::util::DateInterval;
+use crate::util::{DateInterval, DateRoll};
 use chrono::naive::NaiveDate as Date;
-use chrono::Datelike;
+use chrono::{Datelike, Days, Weekday};
 use currency::Currency;
 
 /// Representation of a transaction of some value from a source account to a sink account. Occurs
 /// on at least one date.
-#[derive(Eq, PartialEq, Clone, Debug, Default, Hash)]
+#[derive(Eq, PartialEq, Clone, Debug, Default, Hash, serde::Serialize, serde::Deserialize)]
 pub struct Transaction {
     /// The value transferred by the transaction.
+    #[serde(with = "crate::util::serde_currency")]
     pub value: Currency,
 
     /// The ID of an account which is the source of the transaction, from which the value is taken.
@@ -21,6 +22,7 @@ pub struct Transaction {
 
     /// The date of the first occurrence of this transaction. If no repetition is specified it will
     /// be the only occurrence of the transaction.
+    #[serde(with = "crate::util::serde_date")]
     start: Date,
 
     /// How the transaction repeats, if at all. Will be used in conjunction with `start` (and
@@ -29,7 +31,12 @@ pub struct Transaction {
 
     /// The end date of the transaction. Does not necessarily have to be one of the potential dates
     /// of occurrence but must not be before `start`. No transactions occur after this date.
+    #[serde(default)]
+    #[serde(with = "crate::util::serde_date::option")]
     end: Option<Date>,
+
+    /// How an occurrence landing on a weekend should be adjusted, if at all.
+    roll: DateRoll,
 }
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
@@ -54,11 +61,11 @@ impl Transaction {
         sink: AccountID,
         date: Date,
     ) -> Result<Transaction, TransactionError> {
-        // Ensure we have valid accounts.
-        if !sim.accounts.contains_key(&source) {
+        // Ensure we have valid accounts, cash or asset.
+        if !sim.accounts.contains_key(&source) && !sim.assets.contains_key(&source) {
             return Err(TransactionError::InvalidAccountID { id: source });
         }
-        if !sim.accounts.contains_key(&sink) {
+        if !sim.accounts.contains_key(&sink) && !sim.assets.contains_key(&sink) {
             return Err(TransactionError::InvalidAccountID { id: sink });
         }
 
@@ -77,6 +84,7 @@ impl Transaction {
             start: date,
             rpt: None,
             end: None,
+            roll: DateRoll::None,
         })
     }
 
@@ -123,24 +131,162 @@ impl Transaction {
         Ok(t)
     }
 
+    /// Set how an occurrence landing on a weekend should be adjusted.
+    pub fn with_roll(mut self, roll: DateRoll) -> Transaction {
+        self.roll = roll;
+        self
+    }
+
+    /// Determine whether, ignoring `roll`, this transaction's interval would land on `date`.
+    fn interval_occurs(&self, date: Date) -> bool {
+        match self.rpt {
+            Some(DateInterval::Daily) => true,
+            Some(DateInterval::Weekly) => date.weekday() == self.start.weekday(),
+            Some(DateInterval::Monthly) => {
+                date.day() == self.start.day().min(days_in_month(date.year(), date.month()))
+            }
+            Some(DateInterval::Yearly) => {
+                date.month() == self.start.month() && date.day() == self.start.day()
+            }
+            Some(DateInterval::EveryNDays(n)) => {
+                n > 0 && (date - self.start).num_days() % i64::from(n) == 0
+            }
+            Some(DateInterval::NthWeekdayOfMonth { week, weekday }) => {
+                date.weekday() == weekday && (date.day() - 1) / 7 + 1 == u32::from(week)
+            }
+            None => date == self.start,
+        }
+    }
+
     /// Determine whether this transaction occurs on a specific date. Returns true if the
     /// transaction takes place.
     pub fn occurs(self: &Transaction, date: Date) -> bool {
-        // Transactions cannot occur before their start date.
-        //
-        // If the transaction repeats, then we need to determine if the date is one of
-        // those dates on which it repeats according to the date interval. If the transaction does
-        // not repeat, then it only occurs on the start date.
-        //
-        // If we have an end date, the transaction cannot occur after the end date.
-        date >= self.start
-            && match self.rpt {
-                Some(DateInterval::Daily) => true,
-                Some(DateInterval::Weekly) => date.weekday() == self.start.weekday(),
-                Some(DateInterval::Monthly) => date.day() == self.start.day(),
-                Some(DateInterval::Yearly) => date.ordinal() == self.start.ordinal(),
-                None => date == self.start,
+        // Transactions cannot occur before their start date, or on or after their end date, if
+        // they have one.
+        if date < self.start || self.end.map_or(false, |e| date >= e) {
+            return false;
+        }
+
+        match self.roll {
+            DateRoll::None => self.interval_occurs(date),
+            DateRoll::BusinessDay => {
+                if date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun {
+                    // Occurrences never fire on the weekend day itself; they're rolled forward.
+                    return false;
+                }
+                // `date` occurs either because the interval lands on it directly, or because the
+                // interval landed on the weekend immediately before it and rolled forward here.
+                // A weekend is at most two days long, so only those two days need checking.
+                self.interval_occurs(date)
+                    || (1..=2u64).any(|days_back| {
+                        let candidate = date - Days::new(days_back);
+                        candidate >= self.start
+                            && (candidate.weekday() == Weekday::Sat
+                                || candidate.weekday() == Weekday::Sun)
+                            && self.interval_occurs(candidate)
+                    })
             }
-            && self.end.map_or(true, |e| date < e)
+        }
+    }
+}
+
+/// The number of days in a given month of a given year.
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    let first_of_next_month = Date::from_ymd_opt(next_year, next_month, 1).unwrap();
+    (first_of_next_month - Days::new(1)).day()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::AccountSpec;
+
+    /// A `Simulation` with two cash accounts, for constructing `Transaction`s against.
+    fn sim_with_accounts() -> Simulation {
+        let mut sim = Simulation::default();
+        for id in [0u64, 1u64] {
+            sim.accounts.insert(
+                AccountID { id_val: id },
+                AccountSpec {
+                    name: format!("Account {id}"),
+                    initial_value: Currency::from_str("£0.00").unwrap(),
+                    currency_code: "GBP".to_string(),
+                    interest: 0.0,
+                    out_charge: 0.0,
+                    in_charge: 0.0,
+                },
+            );
+        }
+        sim
+    }
+
+    fn monthly_transaction(sim: &Simulation, start: Date) -> Transaction {
+        Transaction::repeating(
+            sim,
+            Currency::from_str("£10.00").unwrap(),
+            AccountID { id_val: 0 },
+            AccountID { id_val: 1 },
+            start,
+            DateInterval::Monthly,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn monthly_clamps_to_february_in_a_non_leap_year() {
+        let sim = sim_with_accounts();
+        let t = monthly_transaction(&sim, Date::from_ymd_opt(2023, 1, 31).unwrap());
+
+        assert!(t.occurs(Date::from_ymd_opt(2023, 2, 28).unwrap()));
+        assert!(!t.occurs(Date::from_ymd_opt(2023, 2, 27).unwrap()));
+    }
+
+    #[test]
+    fn monthly_clamps_to_february_in_a_leap_year() {
+        let sim = sim_with_accounts();
+        let t = monthly_transaction(&sim, Date::from_ymd_opt(2024, 1, 31).unwrap());
+
+        assert!(t.occurs(Date::from_ymd_opt(2024, 2, 29).unwrap()));
+        assert!(!t.occurs(Date::from_ymd_opt(2024, 2, 28).unwrap()));
+    }
+
+    #[test]
+    fn monthly_clamps_to_a_thirty_day_month() {
+        let sim = sim_with_accounts();
+        let t = monthly_transaction(&sim, Date::from_ymd_opt(2023, 1, 31).unwrap());
+
+        assert!(t.occurs(Date::from_ymd_opt(2023, 4, 30).unwrap()));
+        assert!(!t.occurs(Date::from_ymd_opt(2023, 4, 29).unwrap()));
+    }
+
+    #[test]
+    fn business_day_roll_moves_a_weekend_occurrence_to_monday() {
+        let sim = sim_with_accounts();
+        let t = Transaction::repeating(
+            &sim,
+            Currency::from_str("£10.00").unwrap(),
+            AccountID { id_val: 0 },
+            AccountID { id_val: 1 },
+            Date::from_ymd_opt(2023, 2, 25).unwrap(), // a Saturday
+            DateInterval::Weekly,
+        )
+        .unwrap()
+        .with_roll(DateRoll::BusinessDay);
+
+        assert!(!t.occurs(Date::from_ymd_opt(2023, 2, 25).unwrap())); // Saturday: rolled away
+        assert!(!t.occurs(Date::from_ymd_opt(2023, 2, 26).unwrap())); // Sunday: still weekend
+        assert!(t.occurs(Date::from_ymd_opt(2023, 2, 27).unwrap())); // Monday: rolled forward to
+    }
+
+    #[test]
+    fn days_in_month_accounts_for_leap_years() {
+        assert_eq!(days_in_month(2023, 2), 28);
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2023, 4), 30);
     }
 }
@@ -1,19 +1,42 @@
+use chrono::Weekday;
 use currency::Currency;
 use num::ToPrimitive;
 
 /// An enum defining different intervals between dates for use when defining repeating events.
-#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash)]
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash, serde::Serialize, serde::Deserialize)]
 pub enum DateInterval {
     /// Event occurs once every day.
     Daily,
     /// Event occurs once every week.
     Weekly,
-    /// Event occurs once every month.
+    /// Event occurs once every month, on the same day of the month as the start date. Clamped to
+    /// the last valid day of the month for start dates which don't exist in a shorter month, e.g.
+    /// an event starting on 31 January occurs on 28 (or 29) February.
     Monthly,
-    /// Event occurs once every year.
+    /// Event occurs once every year, on the same month and day as the start date.
     Yearly,
+    /// Event occurs every `n` days, counting from the start date.
+    EveryNDays(u32),
+    /// Event occurs on the `week`th occurrence of `weekday` in every month (1-indexed, so
+    /// `week: 1` is the first such weekday of the month). A `week` beyond the number of times
+    /// `weekday` occurs in a given month means the event simply doesn't occur that month.
+    NthWeekdayOfMonth {
+        week: u8,
+        #[serde(with = "crate::util::serde_weekday")]
+        weekday: Weekday,
+    },
+}
 
-    // TODO: Custom interval.
+/// How a recurring event's computed occurrence date should be adjusted before it's treated as
+/// actually occurring.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash, Default, serde::Serialize, serde::Deserialize)]
+pub enum DateRoll {
+    /// The event occurs exactly on its computed date, even if that's a weekend.
+    #[default]
+    None,
+    /// If the event's computed date falls on a weekend, it's rolled forward to occur on the next
+    /// weekday instead.
+    BusinessDay,
 }
 
 /// Create a floating point value representing a currency value so that we can do higher precision
@@ -24,9 +47,121 @@ pub fn to_f64(val: &Currency) -> f64 {
 
 /// Convert a floating point value to a currency value, rounding off to the precision of two
 /// decimal places.
-pub fn to_currency(val: f64) -> Currency {
-    match Currency::from_str(&format!("£{:.2}", val).to_string()) {
+pub fn to_currency(val: f64, symbol: &str) -> Currency {
+    match Currency::from_str(&format!("{}{:.2}", symbol, val)) {
         Ok(v) => v,
         Err(_) => Currency::new(),
     }
 }
+
+/// Look up the symbol conventionally used to denote a given ISO 4217 currency code, for use with
+/// [`to_currency`]. Falls back to `£` for unrecognised codes, matching this crate's original
+/// GBP-only behaviour.
+pub fn symbol_for_currency_code(code: &str) -> &'static str {
+    match code {
+        "USD" => "$",
+        "EUR" => "€",
+        "JPY" => "¥",
+        "GBP" => "£",
+        _ => "£",
+    }
+}
+
+/// Serde (de)serialization for `Currency` values, since `currency::Currency` has no `Serialize` or
+/// `Deserialize` implementation of its own. Values round-trip through the same string form used
+/// everywhere else, e.g. `"£1000.00"`.
+pub mod serde_currency {
+    use currency::Currency;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S>(val: &Currency, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        val.to_string().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Currency, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Currency::from_str(&s)
+            .map_err(|_| serde::de::Error::custom(format!("invalid currency value: {}", s)))
+    }
+}
+
+/// Serde (de)serialization for `Weekday` values, since `chrono::Weekday` has no `Serialize` or
+/// `Deserialize` implementation without chrono's `serde` feature, which this crate doesn't enable.
+/// Values round-trip through chrono's own short string form, e.g. `"Mon"`.
+pub mod serde_weekday {
+    use chrono::Weekday;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S>(val: &Weekday, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        val.to_string().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Weekday, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<Weekday>()
+            .map_err(|_| serde::de::Error::custom(format!("invalid weekday: {}", s)))
+    }
+}
+
+/// Serde (de)serialization for `NaiveDate` values as ISO-8601 (`YYYY-MM-DD`) strings.
+pub mod serde_date {
+    use chrono::naive::NaiveDate as Date;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    const FORMAT: &str = "%Y-%m-%d";
+
+    pub fn serialize<S>(date: &Date, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        date.format(FORMAT).to_string().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Date, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Date::parse_from_str(&s, FORMAT).map_err(serde::de::Error::custom)
+    }
+
+    /// As above, for the `Option<NaiveDate>` fields used by transaction end dates.
+    pub mod option {
+        use chrono::naive::NaiveDate as Date;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        use super::FORMAT;
+
+        pub fn serialize<S>(date: &Option<Date>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            date.map(|d| d.format(FORMAT).to_string())
+                .serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Date>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            match Option::<String>::deserialize(deserializer)? {
+                Some(s) => Ok(Some(
+                    Date::parse_from_str(&s, FORMAT).map_err(serde::de::Error::custom)?,
+                )),
+                None => Ok(None),
+            }
+        }
+    }
+}